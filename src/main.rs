@@ -1,26 +1,57 @@
+use async_trait::async_trait;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
 use cap_directories::{ambient_authority, ProjectDirs};
 use cap_primitives::fs::OpenOptions;
 use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 use notify_rust::Notification as SystemNotification;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::io::Write;
 use std::sync::mpsc;
-use std::{env, process::Command};
+use std::sync::Arc;
+use std::env;
 use tokio::time::{sleep, Duration};
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 const GITHUB_API: &str = "https://api.github.com/notifications";
 
 const LAST_UPDATED_STATE_FILE: &str = "last_updated";
 
+const SEEN_STATE_FILE: &str = "seen.json";
+
+const DEFAULT_SEEN_RETENTION_DAYS: i64 = 30;
+
+const DEFAULT_WEBHOOK_PATH: &str = "/webhook";
+const DEFAULT_WEBHOOK_ADDR: &str = "0.0.0.0:3000";
+
+type HmacSha256 = Hmac<Sha256>;
+
 #[derive(Clone)]
 struct Notifier {
     token: String,
     client: Client,
 }
 
+/// Outcome of a single conditional poll. `notifications` is `None` when the
+/// server answered `304 Not Modified`; `etag`/`last_modified` carry the
+/// validators to send next time and `poll_interval` the server-requested wait.
+struct PollResponse {
+    notifications: Option<Vec<Notification>>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    poll_interval: Duration,
+}
+
 // https://docs.github.com/en/rest/activity/notifications?apiVersion=2022-11-28#about-notification-reasons
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -60,7 +91,7 @@ struct Repository {
     full_name: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct Subject {
     title: String,
     url: String,
@@ -69,6 +100,341 @@ struct Subject {
     subject_type: String,
 }
 
+/// The slice of a GitHub webhook payload we care about. Every event type we
+/// handle carries a `repository`; the remaining fields are pulled out per
+/// event in [`WebhookEvent::into_notification`].
+#[derive(Deserialize, Debug)]
+struct WebhookPayload {
+    repository: Repository,
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    pull_request: Option<WebhookLinked>,
+    #[serde(default)]
+    issue: Option<WebhookLinked>,
+    #[serde(default)]
+    comment: Option<WebhookComment>,
+    #[serde(default)]
+    compare: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct WebhookLinked {
+    title: String,
+    html_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct WebhookComment {
+    html_url: String,
+}
+
+/// A decoded GitHub webhook delivery, identified by the `X-GitHub-Event`
+/// header. Unknown event names map to [`WebhookEvent::Other`] so the server
+/// still acknowledges the delivery instead of 400ing.
+#[derive(Debug)]
+enum WebhookEvent {
+    Push,
+    PullRequest,
+    IssueComment,
+    Issues,
+    Other(String),
+}
+
+impl WebhookEvent {
+    fn from_header(event: &str) -> Self {
+        match event {
+            "push" => WebhookEvent::Push,
+            "pull_request" => WebhookEvent::PullRequest,
+            "issue_comment" => WebhookEvent::IssueComment,
+            "issues" => WebhookEvent::Issues,
+            other => WebhookEvent::Other(other.to_owned()),
+        }
+    }
+
+    /// Project the raw payload onto the [`Notification`] shape used by the
+    /// polling path so both modes share [`Dispatcher::dispatch`].
+    fn into_notification(self, payload: WebhookPayload) -> Notification {
+        let subject_type = match &self {
+            WebhookEvent::Push => "Push",
+            WebhookEvent::PullRequest => "PullRequest",
+            WebhookEvent::IssueComment => "IssueComment",
+            WebhookEvent::Issues => "Issue",
+            WebhookEvent::Other(name) => return Notification::generic(name, payload),
+        }
+        .to_owned();
+
+        let (title, url) = match &self {
+            WebhookEvent::Push => (
+                format!("New push to {}", payload.repository.full_name),
+                payload.compare.clone(),
+            ),
+            WebhookEvent::PullRequest => payload
+                .pull_request
+                .as_ref()
+                .map(|pr| (pr.title.clone(), Some(pr.html_url.clone())))
+                .unwrap_or_default(),
+            WebhookEvent::Issues => payload
+                .issue
+                .as_ref()
+                .map(|i| (i.title.clone(), Some(i.html_url.clone())))
+                .unwrap_or_default(),
+            WebhookEvent::IssueComment => {
+                let title = payload
+                    .issue
+                    .as_ref()
+                    .map(|i| i.title.clone())
+                    .unwrap_or_else(|| "New comment".to_owned());
+                let url = payload
+                    .comment
+                    .as_ref()
+                    .map(|c| c.html_url.clone())
+                    .or_else(|| payload.issue.as_ref().map(|i| i.html_url.clone()));
+                (title, url)
+            }
+            WebhookEvent::Other(_) => unreachable!(),
+        };
+
+        let reason = match &self {
+            WebhookEvent::IssueComment => Reason::Comment,
+            WebhookEvent::PullRequest | WebhookEvent::Issues => Reason::StateChange,
+            _ => Reason::Subscribed,
+        };
+
+        let url = url.unwrap_or_else(|| payload.repository.full_name.clone());
+        Notification {
+            id: format!("{}:{}", payload.repository.id, subject_type),
+            reason,
+            repository: payload.repository,
+            subject: Subject {
+                title,
+                url: url.clone(),
+                latest_comment_url: None,
+                subject_type,
+            },
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+impl Notification {
+    /// Fallback mapping for webhook event types we don't model explicitly.
+    fn generic(event: &str, payload: WebhookPayload) -> Notification {
+        Notification {
+            id: format!("{}:{}", payload.repository.id, event),
+            reason: Reason::Subscribed,
+            subject: Subject {
+                title: format!("{} event", event),
+                url: payload.repository.full_name.clone(),
+                latest_comment_url: None,
+                subject_type: event.to_owned(),
+            },
+            updated_at: Utc::now().to_rfc3339(),
+            repository: payload.repository,
+        }
+    }
+}
+
+/// Shared state for the axum webhook handler.
+#[derive(Clone)]
+struct WebhookState {
+    dispatcher: Arc<Dispatcher>,
+    secret: String,
+}
+
+/// A triage action that can be applied to a notification thread through the
+/// GitHub Notifications REST API.
+#[derive(Clone, Copy, Debug)]
+enum ThreadAction {
+    /// `PATCH /notifications/threads/{id}` — mark the thread as read.
+    MarkRead,
+    /// `DELETE /notifications/threads/{id}` — mark the thread as done.
+    Done,
+    /// `DELETE /notifications/threads/{id}/subscription` — unsubscribe.
+    Unsubscribe,
+}
+
+/// What the user picked on the toast, carrying the data needed to act on it.
+enum NotificationAction {
+    Open(Subject),
+    Thread(String, ThreadAction),
+}
+
+/// Whether `id` is a real GitHub notification thread id (a plain integer) as
+/// returned by the polling API, rather than a synthetic id minted for a
+/// webhook event.
+fn is_thread_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// A destination a [`Notification`] can be delivered to. Several sinks may be
+/// active at once so headless deployments still get alerted.
+#[async_trait]
+trait NotificationSink: Send + Sync {
+    async fn deliver(&self, notification: &Notification) -> Result<(), anyhow::Error>;
+}
+
+/// Fans a notification out to every configured [`NotificationSink`], logging
+/// but not propagating individual sink failures so one broken channel can't
+/// mask the others.
+struct Dispatcher {
+    sinks: Vec<Arc<dyn NotificationSink>>,
+}
+
+impl Dispatcher {
+    async fn dispatch(&self, notification: Notification) {
+        debug!(
+            "Notifying about '{}' ('{}')",
+            notification.id, notification.subject.title
+        );
+        for sink in &self.sinks {
+            if let Err(e) = sink.deliver(&notification).await {
+                error!("Sink delivery failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Desktop-toast sink: the original interactive notification, including the
+/// "Open"/"Mark read"/"Done"/"Unsubscribe" actions wired back to GitHub.
+struct DesktopSink {
+    notifier: Arc<Notifier>,
+}
+
+#[async_trait]
+impl NotificationSink for DesktopSink {
+    async fn deliver(&self, notification: &Notification) -> Result<(), anyhow::Error> {
+        let (tx, rx) = mpsc::channel();
+
+        let id = notification.id.clone();
+        let subject = notification.subject.clone();
+
+        let mut toast = SystemNotification::new();
+        toast
+            .summary(&notification.repository.full_name)
+            .appname("GitHub")
+            .body(&format!(
+                "{} ({}/{:?})",
+                &notification.subject.title, notification.subject.subject_type, notification.reason
+            ))
+            .action("default", "Open");
+        // Thread triage only works against real notification thread ids, which
+        // the polling path supplies. Webhook-originated notifications carry a
+        // synthetic id (see `WebhookEvent::into_notification`), so omit the
+        // actions that would otherwise 404 against the REST API.
+        if is_thread_id(&notification.id) {
+            toast
+                .action("mark_read", "Mark read")
+                .action("done", "Done")
+                .action("unsubscribe", "Unsubscribe");
+        }
+        toast
+            .show()?
+            .wait_for_action(move |action| {
+                let chosen = match action {
+                    "default" => NotificationAction::Open(subject),
+                    "mark_read" => NotificationAction::Thread(id, ThreadAction::MarkRead),
+                    "done" => NotificationAction::Thread(id, ThreadAction::Done),
+                    "unsubscribe" => NotificationAction::Thread(id, ThreadAction::Unsubscribe),
+                    _ => return,
+                };
+                tx.send(chosen).unwrap();
+            });
+
+        match rx.recv() {
+            Ok(NotificationAction::Open(subject)) => {
+                if let Err(e) = self.notifier.open_browser(subject).await {
+                    error!("Failed to open URL: {}", e);
+                }
+            }
+            Ok(NotificationAction::Thread(id, action)) => {
+                if let Err(e) = self.notifier.mark_thread(&id, action).await {
+                    error!("Failed to apply {:?} to thread {}: {}", action, id, e);
+                }
+            }
+            Err(_) => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// SMTP connection and addressing details for the [`EmailSink`], all read from
+/// the environment.
+struct EmailConfig {
+    host: String,
+    port: u16,
+    username: String,
+    password: String,
+    from: Mailbox,
+    to: Mailbox,
+}
+
+impl EmailConfig {
+    fn from_env() -> Result<Self, anyhow::Error> {
+        Ok(EmailConfig {
+            host: env::var("SMTP_HOST")?,
+            port: env::var("SMTP_PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(587),
+            username: env::var("SMTP_USERNAME")?,
+            password: env::var("SMTP_PASSWORD")?,
+            from: env::var("EMAIL_FROM")?.parse()?,
+            to: env::var("EMAIL_TO")?.parse()?,
+        })
+    }
+}
+
+/// Email sink for headless/server deployments without a desktop session.
+struct EmailSink {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Mailbox,
+}
+
+impl EmailSink {
+    fn new(config: EmailConfig) -> Result<Self, anyhow::Error> {
+        // Default port is 587 (submission), so use STARTTLS to match; an
+        // implicit-TLS `relay()` would only be correct on port 465.
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            .port(config.port)
+            .credentials(Credentials::new(config.username, config.password))
+            .build();
+        Ok(EmailSink {
+            transport,
+            from: config.from,
+            to: config.to,
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationSink for EmailSink {
+    async fn deliver(&self, notification: &Notification) -> Result<(), anyhow::Error> {
+        let link = notification
+            .subject
+            .latest_comment_url
+            .as_ref()
+            .unwrap_or(&notification.subject.url);
+        let body = format!(
+            "{}\n\nType: {}\nReason: {:?}\n\n{}",
+            notification.subject.title,
+            notification.subject.subject_type,
+            notification.reason,
+            link
+        );
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(self.to.clone())
+            .subject(notification.repository.full_name.clone())
+            .body(body)?;
+
+        self.transport.send(email).await?;
+        Ok(())
+    }
+}
+
 impl Notifier {
     pub fn new(token: String) -> Self {
         Notifier {
@@ -77,89 +443,193 @@ impl Notifier {
         }
     }
 
-    pub async fn start(&self) {
+    pub async fn start(&self, dispatcher: Arc<Dispatcher>) {
         let mut last_updated = read_last_updated();
+        let mut seen = SeenStore::load();
+        let mut etag: Option<String> = None;
+        let mut last_modified: Option<String> = None;
         info!("Notifier started. Last updated date: {}", last_updated);
 
         loop {
             let update_time = Utc::now();
-            match self.fetch_github_notifications().await {
-                Ok(notifications) => {
-                    let mut handles = Vec::new();
-                    for notification in notifications {
-                        let updated_at = DateTime::parse_from_rfc3339(&notification.updated_at)
-                            .unwrap()
-                            .with_timezone(&Utc);
-
-                        if updated_at > last_updated {
-                            let notifier_clone = self.clone();
-                            let handle = tokio::spawn(async move {
-                                notifier_clone.handle_notification(notification).await
-                            });
-                            handles.push(handle)
-                        }
+            let mut next_sleep = Duration::from_secs(30);
+
+            match self
+                .fetch_github_notifications(last_updated, etag.as_deref(), last_modified.as_deref())
+                .await
+            {
+                Ok(response) => {
+                    next_sleep = response.poll_interval;
+                    // GitHub may omit the validators on a 304; keep the old ones.
+                    if response.etag.is_some() {
+                        etag = response.etag;
+                    }
+                    if response.last_modified.is_some() {
+                        last_modified = response.last_modified;
                     }
 
-                    for handle in handles {
-                        handle.await.unwrap();
+                    match response.notifications {
+                        None => trace!("No new notifications (304 Not Modified)"),
+                        Some(notifications) => {
+                            let mut handles = Vec::new();
+                            for notification in notifications {
+                                let updated_at = match DateTime::parse_from_rfc3339(
+                                    &notification.updated_at,
+                                ) {
+                                    Ok(updated_at) => updated_at.with_timezone(&Utc),
+                                    Err(e) => {
+                                        error!(
+                                            "Skipping notification {} with unparseable updated_at '{}': {}",
+                                            notification.id, notification.updated_at, e
+                                        );
+                                        continue;
+                                    }
+                                };
+
+                                // Exactly-once-per-update: only alert when this
+                                // id's timestamp is strictly newer than last shown.
+                                if seen.should_alert(&notification.id, updated_at) {
+                                    let dispatcher = dispatcher.clone();
+                                    let handle = tokio::spawn(async move {
+                                        dispatcher.dispatch(notification).await
+                                    });
+                                    handles.push(handle)
+                                }
+                            }
+
+                            for handle in handles {
+                                handle.await.unwrap();
+                            }
+
+                            seen.prune(seen_retention());
+                            seen.save();
+
+                            last_updated = update_time;
+                            write_last_updated(last_updated);
+                        }
                     }
                 }
                 Err(e) => error!("Error fetching notifications: {}", e),
             }
 
-            last_updated = update_time;
-            write_last_updated(last_updated);
-
-            sleep(Duration::from_secs(30)).await;
+            sleep(next_sleep).await;
         }
     }
 
-    async fn fetch_github_notifications(&self) -> Result<Vec<Notification>, reqwest::Error> {
-        let res = self
+    /// Run as a push-driven webhook receiver instead of polling. Listens on
+    /// `GITHUB_WEBHOOK_ADDR` (default `0.0.0.0:3000`) and serves the configured
+    /// path (`GITHUB_WEBHOOK_PATH`, default `/webhook`). Deliveries are only
+    /// processed after their `X-Hub-Signature-256` is verified against
+    /// `GITHUB_WEBHOOK_SECRET`.
+    pub async fn start_webhook(&self, dispatcher: Arc<Dispatcher>) {
+        let secret =
+            env::var("GITHUB_WEBHOOK_SECRET").expect("GITHUB_WEBHOOK_SECRET not set");
+        let path = env::var("GITHUB_WEBHOOK_PATH")
+            .unwrap_or_else(|_| DEFAULT_WEBHOOK_PATH.to_owned());
+        let addr = env::var("GITHUB_WEBHOOK_ADDR")
+            .unwrap_or_else(|_| DEFAULT_WEBHOOK_ADDR.to_owned());
+
+        let state = WebhookState { dispatcher, secret };
+        let app = Router::new()
+            .route(&path, post(webhook_handler))
+            .with_state(state);
+
+        info!("Webhook receiver listening on {}{}", addr, path);
+        let listener = tokio::net::TcpListener::bind(&addr)
+            .await
+            .expect("Failed to bind webhook listener");
+        axum::serve(listener, app)
+            .await
+            .expect("Webhook server terminated");
+    }
+
+    /// Poll the notifications endpoint using GitHub's documented contract:
+    /// scope the query with `?since=`, send the stored `ETag`/`Last-Modified`
+    /// as conditional-request validators, and report back the fresh validators
+    /// plus the server-requested poll interval. A `304 Not Modified` response
+    /// costs no rate-limit quota and yields `notifications: None`.
+    async fn fetch_github_notifications(
+        &self,
+        since: DateTime<Utc>,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) -> Result<PollResponse, reqwest::Error> {
+        let mut request = self
             .client
             .get(GITHUB_API)
+            .query(&[("since", since.to_rfc3339())])
             .header("Authorization", format!("token {}", self.token))
             .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "request")
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Vec<Notification>>()
-            .await?;
-
-        Ok(res)
-    }
+            .header("User-Agent", "request");
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
 
-    async fn handle_notification(&self, notification: Notification) {
-        let (tx, rx) = mpsc::channel();
+        let res = request.send().await?.error_for_status()?;
 
-        debug!("Notifying about '{}' ('{}')", notification.id, notification.subject.title);
+        let header = |name: reqwest::header::HeaderName| {
+            res.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        let poll_interval = res
+            .headers()
+            .get("X-Poll-Interval")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| Duration::from_secs(30));
 
-        // Display the notification
-        SystemNotification::new()
-            .summary(&notification.repository.full_name)
-            .appname("GitHub")
-            .body(&format!(
-                "{} ({}/{:?})",
-                &notification.subject.title, notification.subject.subject_type, notification.reason
-            ))
-            .action("default", "Open")
-            .show()
-            .unwrap()
-            .wait_for_action(move |action| {
-                if action == "default" {
-                    let tx_clone = tx.clone();
-                    tx_clone.send(notification.subject).unwrap();
-                }
+        if res.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(PollResponse {
+                notifications: None,
+                etag,
+                last_modified,
+                poll_interval,
             });
-
-        // Wait for action and handle it
-        if let Ok(subject) = rx.recv() {
-            self.open_browser(subject).await;
         }
+
+        let notifications = res.json::<Vec<Notification>>().await?;
+        Ok(PollResponse {
+            notifications: Some(notifications),
+            etag,
+            last_modified,
+            poll_interval,
+        })
     }
 
-    async fn open_browser(&self, subject: Subject) {
+    /// Apply a triage [`ThreadAction`] to a notification thread using the same
+    /// authenticated headers as the rest of the client.
+    async fn mark_thread(&self, id: &str, action: ThreadAction) -> Result<(), reqwest::Error> {
+        let base = format!("https://api.github.com/notifications/threads/{}", id);
+        let request = match action {
+            ThreadAction::MarkRead => self.client.patch(&base),
+            ThreadAction::Done => self.client.delete(&base),
+            ThreadAction::Unsubscribe => {
+                self.client.delete(format!("{}/subscription", base))
+            }
+        };
+
+        trace!("Applying {:?} to thread {}", action, id);
+        request
+            .header("Authorization", format!("token {}", self.token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "request")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn open_browser(&self, subject: Subject) -> Result<(), anyhow::Error> {
         let url = if let Some(url) = subject.latest_comment_url {
             url
         } else {
@@ -168,7 +638,7 @@ impl Notifier {
 
         trace!("Notify URL for '{}': {}", subject.title, url);
 
-        let res = self
+        let html_url = self
             .client
             .get(url)
             .header("Authorization", format!("token {}", self.token))
@@ -176,24 +646,75 @@ impl Notifier {
             .header("X-GitHub-Api-Version", "2022-11-28")
             .header("User-Agent", "request")
             .send()
-            .await
-            .unwrap()
+            .await?
             .json::<DetailItem>()
-            .await
-            .unwrap()
+            .await?
             .html_url;
 
-        tokio::task::spawn_blocking(move || {
-            Command::new("xdg-open")
-                .arg(res)
-                .spawn()
-                .expect("Failed to open URL")
-        })
-        .await
-        .unwrap();
+        // `open::that` resolves the platform default browser (xdg-open on
+        // Linux, `open` on macOS, `ShellExecute` on Windows) and surfaces an
+        // error instead of panicking when no opener is available.
+        tokio::task::spawn_blocking(move || open::that(html_url)).await??;
+        Ok(())
     }
 }
 
+/// Verify a GitHub delivery signature. GitHub sends `sha256=<hex>` in
+/// `X-Hub-Signature-256`, computed as `HMAC-SHA256(secret, raw_body)`. The
+/// comparison runs in constant time via [`Mac::verify_slice`]. The body must
+/// be the raw bytes received — re-serializing the JSON would change them and
+/// break verification.
+fn verify_signature(secret: &str, signature: Option<&str>, body: &[u8]) -> bool {
+    let signature = match signature.and_then(|s| s.strip_prefix("sha256=")) {
+        Some(hex) => hex,
+        None => return false,
+    };
+    let expected = match hex::decode(signature) {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+async fn webhook_handler(
+    State(state): State<WebhookState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let signature = headers
+        .get("X-Hub-Signature-256")
+        .and_then(|v| v.to_str().ok());
+
+    if !verify_signature(&state.secret, signature, &body) {
+        warn!("Rejecting webhook delivery with missing or invalid signature");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let event = headers
+        .get("X-GitHub-Event")
+        .and_then(|v| v.to_str().ok())
+        .map(WebhookEvent::from_header)
+        .unwrap_or_else(|| WebhookEvent::Other("unknown".to_owned()));
+
+    let payload = match serde_json::from_slice::<WebhookPayload>(&body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to parse webhook payload: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    let dispatcher = state.dispatcher.clone();
+    let notification = event.into_notification(payload);
+    tokio::spawn(async move { dispatcher.dispatch(notification).await });
+
+    StatusCode::OK
+}
+
 fn write_last_updated(datetime: DateTime<Utc>) {
     if let Some(proj_dirs) =
         ProjectDirs::from("com.github", "lfrancke", "gh-notifier", ambient_authority())
@@ -210,6 +731,77 @@ fn write_last_updated(datetime: DateTime<Utc>) {
     }
 }
 
+/// Per-notification dedup store. Maps a `notification.id` to the `updated_at`
+/// we last alerted for, so a thread whose timestamp keeps bumping (e.g. new
+/// comments) only re-alerts when it is strictly newer than what we've seen.
+/// Persisted as JSON in the cache dir so it survives restarts and upgrades.
+#[derive(Default, Serialize, Deserialize)]
+struct SeenStore {
+    entries: std::collections::HashMap<String, DateTime<Utc>>,
+}
+
+impl SeenStore {
+    fn load() -> Self {
+        if let Some(proj_dirs) =
+            ProjectDirs::from("com.github", "lfrancke", "gh-notifier", ambient_authority())
+        {
+            let cache_dir = proj_dirs.cache_dir().unwrap();
+            if let Ok(contents) = cache_dir.read_to_string(SEEN_STATE_FILE) {
+                if let Ok(store) = serde_json::from_str(&contents) {
+                    return store;
+                }
+            }
+        }
+
+        SeenStore::default()
+    }
+
+    /// Record that `id` was seen at `updated_at`, returning `true` only if this
+    /// is strictly newer than the previously alerted timestamp for that id.
+    fn should_alert(&mut self, id: &str, updated_at: DateTime<Utc>) -> bool {
+        let fire = self
+            .entries
+            .get(id)
+            .map_or(true, |previous| updated_at > *previous);
+        if fire {
+            self.entries.insert(id.to_owned(), updated_at);
+        }
+        fire
+    }
+
+    /// Drop entries we last alerted for before `now - retention` so the store
+    /// doesn't grow without bound.
+    fn prune(&mut self, retention: chrono::Duration) {
+        let cutoff = Utc::now() - retention;
+        self.entries.retain(|_, updated_at| *updated_at >= cutoff);
+    }
+
+    fn save(&self) {
+        if let Some(proj_dirs) =
+            ProjectDirs::from("com.github", "lfrancke", "gh-notifier", ambient_authority())
+        {
+            let cache_dir = proj_dirs.cache_dir().unwrap();
+            let mut state_file = cache_dir
+                .open_with(
+                    SEEN_STATE_FILE,
+                    OpenOptions::new().create(true).write(true).truncate(true),
+                )
+                .unwrap()
+                .into_std();
+            let contents = serde_json::to_string(self).expect("Failed to serialize seen store");
+            write!(state_file, "{}", contents).expect("Failed to write to file");
+        }
+    }
+}
+
+fn seen_retention() -> chrono::Duration {
+    let days = env::var("SEEN_RETENTION_DAYS")
+        .ok()
+        .and_then(|d| d.parse().ok())
+        .unwrap_or(DEFAULT_SEEN_RETENTION_DAYS);
+    chrono::Duration::days(days)
+}
+
 fn read_last_updated() -> DateTime<Utc> {
     if let Some(proj_dirs) =
         ProjectDirs::from("com.github", "lfrancke", "gh-notifier", ambient_authority())
@@ -236,6 +828,104 @@ async fn main() {
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
     let token = env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN not set");
-    let notifier = Notifier::new(token);
-    notifier.start().await;
+    let notifier = Arc::new(Notifier::new(token));
+
+    // Build the enabled delivery sinks. `GITHUB_SINKS` is a comma-separated
+    // list (default `desktop`); listing several enables them all at once.
+    let enabled = env::var("GITHUB_SINKS").unwrap_or_else(|_| "desktop".to_owned());
+    let mut sinks: Vec<Arc<dyn NotificationSink>> = Vec::new();
+    for name in enabled.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        match name {
+            "desktop" => sinks.push(Arc::new(DesktopSink {
+                notifier: notifier.clone(),
+            })),
+            "email" => {
+                let config = EmailConfig::from_env().expect("Invalid email sink configuration");
+                sinks.push(Arc::new(EmailSink::new(config).expect("Failed to build email sink")));
+            }
+            other => warn!("Ignoring unknown notification sink '{}'", other),
+        }
+    }
+    let dispatcher = Arc::new(Dispatcher { sinks });
+
+    match env::var("GITHUB_MODE").as_deref() {
+        Ok("webhook") => notifier.start_webhook(dispatcher).await,
+        _ => notifier.start(dispatcher).await,
+    }
+}
+
+#[cfg(test)]
+mod signature_tests {
+    use super::*;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn accepts_a_valid_signature() {
+        let signature = sign(SECRET, BODY);
+        assert!(verify_signature(SECRET, Some(&signature), BODY));
+    }
+
+    #[test]
+    fn rejects_a_signature_for_a_different_body() {
+        let signature = sign(SECRET, BODY);
+        assert!(!verify_signature(SECRET, Some(&signature), b"tampered"));
+    }
+
+    #[test]
+    fn rejects_a_signature_without_the_sha256_prefix() {
+        let signature = sign(SECRET, BODY);
+        let hex_only = signature.strip_prefix("sha256=").unwrap();
+        assert!(!verify_signature(SECRET, Some(hex_only), BODY));
+    }
+
+    #[test]
+    fn rejects_a_missing_header() {
+        assert!(!verify_signature(SECRET, None, BODY));
+    }
+}
+
+#[cfg(test)]
+mod seen_store_tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn first_sighting_fires_then_dedups() {
+        let mut store = SeenStore::default();
+        assert!(store.should_alert("1", at(100)));
+        // Same timestamp must not re-fire.
+        assert!(!store.should_alert("1", at(100)));
+        // An older timestamp must not re-fire either.
+        assert!(!store.should_alert("1", at(50)));
+    }
+
+    #[test]
+    fn strictly_newer_timestamp_re_fires() {
+        let mut store = SeenStore::default();
+        assert!(store.should_alert("1", at(100)));
+        assert!(store.should_alert("1", at(200)));
+        // ...and the new high-water mark is what's compared against next.
+        assert!(!store.should_alert("1", at(200)));
+    }
+
+    #[test]
+    fn prune_drops_entries_older_than_retention() {
+        let mut store = SeenStore::default();
+        store.should_alert("old", Utc::now() - chrono::Duration::days(40));
+        store.should_alert("fresh", Utc::now());
+        store.prune(chrono::Duration::days(30));
+        assert!(!store.entries.contains_key("old"));
+        assert!(store.entries.contains_key("fresh"));
+    }
 }